@@ -0,0 +1,237 @@
+//! Layered configuration: built-in defaults, overridden by `vocabpop.toml`
+//! in the platform config directory, overridden in turn by CLI flags.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::Args;
+
+pub const DEFAULT_DIR: &str = "vocab";
+pub const DEFAULT_INTERVAL: u64 = 1;
+pub const DEFAULT_SHUFFLE: bool = true;
+pub const DEFAULT_ICON: &str = "dialog-information";
+pub const DEFAULT_APP_NAME: &str = "VocabPop";
+pub const DEFAULT_URGENCY: &str = "normal";
+
+/// A `start`–`end` window (local `HH:MM`, may wrap past midnight) during
+/// which notifications are suppressed.
+#[derive(Debug, Clone, Deserialize)]
+pub struct QuietHours {
+    pub start: String,
+    pub end: String,
+}
+
+impl QuietHours {
+    /// Whether `now` (local `HH:MM`) falls inside this window.
+    pub fn contains(&self, now: &str) -> bool {
+        let (Some(start), Some(end), Some(now)) = (
+            parse_hhmm(&self.start),
+            parse_hhmm(&self.end),
+            parse_hhmm(now),
+        ) else {
+            return false;
+        };
+        if start <= end {
+            now >= start && now < end
+        } else {
+            // Window wraps past midnight, e.g. 22:00–07:00.
+            now >= start || now < end
+        }
+    }
+}
+
+/// The local UTC offset, captured once while the process is still
+/// single-threaded. `time::OffsetDateTime::now_local` only succeeds under
+/// that guarantee, and this program spawns several threads (clock, stdin,
+/// signals, the watcher) before quiet hours are ever checked — calling it
+/// later would reliably fail and silently fall back to UTC on every real
+/// run. Callers must fetch this at startup, before spawning any thread, and
+/// thread it through to [`current_hhmm`].
+pub fn local_offset() -> time::UtcOffset {
+    time::UtcOffset::current_local_offset().unwrap_or(time::UtcOffset::UTC)
+}
+
+/// Current local time as `HH:MM`, used to check [`QuietHours`]. `offset`
+/// should be [`local_offset`], captured once at startup.
+pub fn current_hhmm(offset: time::UtcOffset) -> String {
+    let now = time::OffsetDateTime::now_utc().to_offset(offset);
+    format!("{:02}:{:02}", now.hour(), now.minute())
+}
+
+fn parse_hhmm(s: &str) -> Option<u32> {
+    let (h, m) = s.split_once(':')?;
+    let h: u32 = h.trim().parse().ok()?;
+    let m: u32 = m.trim().parse().ok()?;
+    if h > 23 || m > 59 {
+        return None;
+    }
+    Some(h * 60 + m)
+}
+
+/// Fields of an [`Entry`](crate::Entry) to include in a notification body.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BodyField {
+    Reading,
+    Meaning,
+    Codes,
+}
+
+fn default_body_fields() -> Vec<BodyField> {
+    vec![BodyField::Reading, BodyField::Meaning, BodyField::Codes]
+}
+
+/// Raw shape of `vocabpop.toml`. Every field is optional so a file only
+/// needs to mention what it wants to override.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Config {
+    pub dir: Option<PathBuf>,
+    pub interval: Option<u64>,
+    pub shuffle: Option<bool>,
+    pub icon: Option<String>,
+    pub app_name: Option<String>,
+    pub urgency: Option<String>,
+
+    /// Relative sampling weight per vocab file name, e.g. `{"n3.txt" = 2.0}`.
+    #[serde(default)]
+    pub file_weights: HashMap<String, f64>,
+
+    pub quiet_hours: Option<QuietHours>,
+
+    #[serde(default = "default_body_fields")]
+    pub body_fields: Vec<BodyField>,
+}
+
+// Hand-written rather than `#[derive(Default)]`: a derived impl would give
+// `body_fields` `Vec::new()`, not `default_body_fields()`, so the common
+// no-config-file path (`Config::load` returning `Config::default()`) would
+// silently show title-only notifications instead of falling back to the
+// same fields the baseline always showed.
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            dir: None,
+            interval: None,
+            shuffle: None,
+            icon: None,
+            app_name: None,
+            urgency: None,
+            file_weights: HashMap::new(),
+            quiet_hours: None,
+            body_fields: default_body_fields(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ConfigError(pub String);
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    /// Path to `vocabpop.toml` in the platform config directory
+    /// (e.g. `~/.config/vocabpop/vocabpop.toml` on Linux).
+    pub fn file_path() -> Option<PathBuf> {
+        directories::ProjectDirs::from("", "", "vocabpop")
+            .map(|dirs| dirs.config_dir().join("vocabpop.toml"))
+    }
+
+    /// Loads `vocabpop.toml` if it exists. Returns built-in defaults
+    /// (`Config::default()`) when there is no config file at all, but fails with
+    /// a clear message if the file exists and is malformed.
+    pub fn load() -> Result<Config, ConfigError> {
+        let Some(path) = Self::file_path() else {
+            return Ok(Config::default());
+        };
+        let text = match fs::read_to_string(&path) {
+            Ok(t) => t,
+            Err(_) => return Ok(Config::default()),
+        };
+        toml::from_str(&text)
+            .map_err(|e| ConfigError(format!("failed to parse {}: {}", path.display(), e)))
+    }
+
+    /// Merges this file config with CLI flags (which win) and built-in
+    /// defaults (which lose), producing the final, fully-resolved settings.
+    pub fn resolve(self, args: &Args) -> ResolvedConfig {
+        ResolvedConfig {
+            dir: args
+                .dir
+                .clone()
+                .or(self.dir)
+                .unwrap_or_else(|| PathBuf::from(DEFAULT_DIR)),
+            interval: args.interval.or(self.interval).unwrap_or(DEFAULT_INTERVAL),
+            shuffle: args.shuffle.or(self.shuffle).unwrap_or(DEFAULT_SHUFFLE),
+            icon: args
+                .icon
+                .clone()
+                .or(self.icon)
+                .unwrap_or_else(|| DEFAULT_ICON.to_string()),
+            app_name: args
+                .app_name
+                .clone()
+                .or(self.app_name)
+                .unwrap_or_else(|| DEFAULT_APP_NAME.to_string()),
+            urgency: args
+                .urgency
+                .clone()
+                .or(self.urgency)
+                .unwrap_or_else(|| DEFAULT_URGENCY.to_string()),
+            file_weights: self.file_weights,
+            quiet_hours: self.quiet_hours,
+            body_fields: self.body_fields,
+        }
+    }
+}
+
+/// The fully merged configuration actually used at runtime.
+#[derive(Debug, Clone)]
+pub struct ResolvedConfig {
+    pub dir: PathBuf,
+    pub interval: u64,
+    pub shuffle: bool,
+    pub icon: String,
+    pub app_name: String,
+    pub urgency: String,
+    pub file_weights: HashMap<String, f64>,
+    pub quiet_hours: Option<QuietHours>,
+    pub body_fields: Vec<BodyField>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_config_keeps_the_default_body_fields() {
+        // `#[derive(Default)]` would give this `Vec::new()` instead, which
+        // is what made every notification regress to title-only whenever
+        // there was no `vocabpop.toml` to opt into the feature.
+        assert_eq!(Config::default().body_fields, default_body_fields());
+        assert!(!Config::default().body_fields.is_empty());
+    }
+
+    #[test]
+    fn quiet_hours_window_wraps_past_midnight() {
+        let q = QuietHours { start: "22:00".to_string(), end: "07:00".to_string() };
+        assert!(q.contains("23:30"));
+        assert!(q.contains("03:00"));
+        assert!(!q.contains("12:00"));
+    }
+
+    #[test]
+    fn quiet_hours_window_within_one_day() {
+        let q = QuietHours { start: "09:00".to_string(), end: "17:00".to_string() };
+        assert!(q.contains("12:00"));
+        assert!(!q.contains("08:59"));
+        assert!(!q.contains("17:00")); // end is exclusive
+    }
+}
@@ -0,0 +1,157 @@
+//! Unified event loop plumbing.
+//!
+//! Everything that can make VocabPop do something — the clock, the tray
+//! menu, stdin, OS signals — is an independent "input source" thread that
+//! owns a [`Writer`] and pushes [`Event`]s. `main` just blocks on a single
+//! [`Reader`] and reacts, instead of juggling a busy-polling sleep loop.
+
+use std::io::BufRead;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+/// Something that happened and that the main loop should react to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// The configured interval elapsed; show the next entry.
+    ClockTimer,
+    /// The user asked to see an entry right now (tray menu or stdin).
+    ShowNow,
+    /// The vocab directory changed and should be reloaded.
+    Reload,
+    /// A recall quality grade (0..=5) for the entry shown most recently,
+    /// per the SM-2 scheduler.
+    Grade(u8),
+    /// Restrict the vocab pool to entries fuzzy-matching this query
+    /// (`None` clears the filter).
+    SetFilter(Option<String>),
+    /// Restrict the vocab pool to entries tagged with any of these codes
+    /// (`None` clears the filter).
+    SetCodes(Option<Vec<String>>),
+    /// Shut down.
+    Quit,
+}
+
+/// The sending half of the event channel. Cheap to clone; every input
+/// source gets its own handle.
+#[derive(Clone)]
+pub struct Writer(mpsc::Sender<Event>);
+
+impl Writer {
+    pub fn send(&self, event: Event) {
+        // The receiver only goes away once the process is exiting, so a
+        // failed send here is not actionable.
+        let _ = self.0.send(event);
+    }
+}
+
+/// The receiving half of the event channel. Owned by the main loop.
+pub struct Reader(mpsc::Receiver<Event>);
+
+impl Reader {
+    pub fn recv(&self) -> Option<Event> {
+        self.0.recv().ok()
+    }
+}
+
+/// Creates a fresh event channel.
+pub fn channel() -> (Writer, Reader) {
+    let (tx, rx) = mpsc::channel();
+    (Writer(tx), Reader(rx))
+}
+
+/// Fires [`Event::ClockTimer`] every `interval`.
+pub fn spawn_clock(writer: Writer, interval: Duration) -> thread::JoinHandle<()> {
+    thread::spawn(move || loop {
+        thread::sleep(interval);
+        writer.send(Event::ClockTimer);
+    })
+}
+
+/// Reads commands from stdin: `show`/`n` to show an entry now, `reload`/`r`
+/// to re-scan the vocab directory, `quit`/`q` to exit, a bare digit `0`-`5`
+/// as an SM-2 recall grade for the entry shown most recently, `filter
+/// <query>` (or bare `filter` to clear) to fuzzy-match the pool, and
+/// `codes <a,b>` (or bare `codes` to clear) to filter by the codes field.
+pub fn spawn_stdin(writer: Writer) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        let stdin = std::io::stdin();
+        for line in stdin.lock().lines() {
+            let Ok(line) = line else { break };
+            let line = line.trim();
+            let lower = line.to_ascii_lowercase();
+            match lower.as_str() {
+                "show" | "n" => writer.send(Event::ShowNow),
+                "reload" | "r" => writer.send(Event::Reload),
+                "quit" | "q" => {
+                    writer.send(Event::Quit);
+                    break;
+                }
+                _ if lower == "filter" || lower.starts_with("filter ") => {
+                    let query = line[6..].trim();
+                    let query = (!query.is_empty()).then(|| query.to_string());
+                    writer.send(Event::SetFilter(query));
+                }
+                _ if lower == "codes" || lower.starts_with("codes ") => {
+                    let list = line[5..].trim();
+                    let codes = (!list.is_empty())
+                        .then(|| list.split(',').map(|c| c.trim().to_string()).collect());
+                    writer.send(Event::SetCodes(codes));
+                }
+                _ => {
+                    if let Ok(q @ 0..=5) = line.parse::<u8>() {
+                        writer.send(Event::Grade(q));
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Fires [`Event::Quit`] on Ctrl-C / SIGTERM.
+pub fn spawn_signals(writer: Writer) {
+    ctrlc::set_handler(move || {
+        writer.send(Event::Quit);
+    })
+    .ok();
+}
+
+/// Sets up the system tray icon (Show now / Quit) and forwards its menu
+/// clicks as events. Previously the tray menu was built but its clicks were
+/// never actually read back, so "Show now" did nothing; this polls
+/// `tray_icon`'s menu event receiver to fix that.
+#[cfg(target_os = "windows")]
+pub fn spawn_tray(writer: Writer) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        use tray_icon::menu::{Menu, MenuEvent, MenuItem};
+        use tray_icon::TrayIcon;
+
+        let mut menu = Menu::new();
+        let show_now = MenuItem::new("Show now", true, None);
+        let quit = MenuItem::new("Quit", true, None);
+        let _ = menu.append(&show_now);
+        let _ = menu.append(&quit);
+        let show_id = show_now.id().clone();
+        let quit_id = quit.id().clone();
+
+        let _tray = match TrayIcon::new(None, None, Some(menu)) {
+            Ok(t) => t,
+            Err(e) => {
+                eprintln!("tray init error: {}", e);
+                return;
+            }
+        };
+
+        let receiver = MenuEvent::receiver();
+        loop {
+            if let Ok(event) = receiver.recv_timeout(Duration::from_millis(500)) {
+                if event.id == show_id {
+                    writer.send(Event::ShowNow);
+                } else if event.id == quit_id {
+                    writer.send(Event::Quit);
+                    break;
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,124 @@
+//! Session history: an append-only log of every notification shown, so
+//! `--stats` can report which words keep tripping the learner up.
+//!
+//! Borrows the shape of a shell history entry — a record carrying a
+//! `start_time` and the outcome of that "session" — applied here to one
+//! vocab notification instead of one shell command.
+
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use time::OffsetDateTime;
+
+/// One shown notification: the word, when it was shown, whether the user
+/// acted on it (graded it), and the SM-2 grade if they graded it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub word: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub start_time: OffsetDateTime,
+    pub acted_on: bool,
+    pub grade: Option<u8>,
+}
+
+fn history_dir() -> Option<PathBuf> {
+    directories::ProjectDirs::from("", "", "vocabpop").map(|dirs| dirs.config_dir().join("history"))
+}
+
+/// The log file for `when`'s month. Rotating by month keeps any single
+/// file small without needing a separate log-rotation dependency.
+fn file_path(when: OffsetDateTime) -> Option<PathBuf> {
+    history_dir().map(|dir| dir.join(format!("{:04}-{:02}.jsonl", when.year(), when.month() as u8)))
+}
+
+/// Appends one history record as a line of JSON.
+pub fn append(entry: &HistoryEntry) {
+    let Some(path) = file_path(entry.start_time) else {
+        return;
+    };
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    let Ok(line) = serde_json::to_string(entry) else {
+        return;
+    };
+    match OpenOptions::new().create(true).append(true).open(&path) {
+        Ok(mut f) => {
+            if let Err(e) = writeln!(f, "{line}") {
+                eprintln!("failed to write history log: {e}");
+            }
+        }
+        Err(e) => eprintln!("failed to open history log {}: {}", path.display(), e),
+    }
+}
+
+/// Reads every history record across all rotated log files.
+pub fn read_all() -> Vec<HistoryEntry> {
+    let mut out = Vec::new();
+    let Some(dir) = history_dir() else {
+        return out;
+    };
+    let Ok(read_dir) = fs::read_dir(&dir) else {
+        return out;
+    };
+    for entry in read_dir.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+            continue;
+        }
+        let Ok(file) = File::open(&path) else { continue };
+        for line in BufReader::new(file).lines().map_while(Result::ok) {
+            if let Ok(e) = serde_json::from_str::<HistoryEntry>(&line) {
+                out.push(e);
+            }
+        }
+    }
+    out
+}
+
+/// Per-word exposure count, last-seen time, and recall accuracy.
+#[derive(Debug, Clone)]
+pub struct WordStats {
+    pub word: String,
+    pub exposures: usize,
+    pub last_seen: OffsetDateTime,
+    pub graded: usize,
+    pub correct: usize,
+}
+
+impl WordStats {
+    /// Recall accuracy among graded exposures, or `None` if none were graded.
+    pub fn accuracy(&self) -> Option<f64> {
+        (self.graded > 0).then(|| self.correct as f64 / self.graded as f64)
+    }
+}
+
+/// Aggregates raw history records into per-word stats, sorted by most
+/// exposures first.
+pub fn compute_stats(history: &[HistoryEntry]) -> Vec<WordStats> {
+    use std::collections::HashMap;
+    let mut by_word: HashMap<&str, WordStats> = HashMap::new();
+    for h in history {
+        let stats = by_word.entry(&h.word).or_insert_with(|| WordStats {
+            word: h.word.clone(),
+            exposures: 0,
+            last_seen: h.start_time,
+            graded: 0,
+            correct: 0,
+        });
+        stats.exposures += 1;
+        if h.start_time > stats.last_seen {
+            stats.last_seen = h.start_time;
+        }
+        if let Some(q) = h.grade {
+            stats.graded += 1;
+            if q >= 3 {
+                stats.correct += 1;
+            }
+        }
+    }
+    let mut stats: Vec<WordStats> = by_word.into_values().collect();
+    stats.sort_by(|a, b| b.exposures.cmp(&a.exposures));
+    stats
+}
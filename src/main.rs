@@ -1,10 +1,18 @@
+mod config;
+mod events;
+mod history;
+mod notifier;
+mod search;
+mod srs;
+mod watcher;
+
 use clap::Parser;
+use events::Event;
+use notifier::{NotificationRequest, Urgency};
 use rand::seq::SliceRandom;
 use std::fs;
 use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, mpsc};
-use std::thread;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 #[derive(Debug)]
@@ -13,29 +21,87 @@ struct Entry {
     reading: Option<String>,
     meaning: Option<String>,
     codes: Option<String>,
+    /// File name the entry was read from, used for per-file weighting.
+    source: String,
 }
 
 #[derive(Parser, Debug)]
 #[command(author, version, about = "Japanese vocabulary notifier in Rust", long_about = None)]
 struct Args {
     /// Vocab directory (text files, one entry per line)
-    #[arg(short, long, default_value = "vocab")]
-    dir: PathBuf,
+    #[arg(short, long)]
+    dir: Option<PathBuf>,
 
     /// Interval in minutes between notifications
-    #[arg(short, long, default_value_t = 1)]
-    interval: u64,
+    #[arg(short, long)]
+    interval: Option<u64>,
 
     /// Show a single notification immediately and exit
     #[arg(long, default_value_t = false)]
     force: bool,
 
     /// Shuffle vocab entries
-    #[arg(long, default_value_t = true)]
-    shuffle: bool,
+    #[arg(long)]
+    shuffle: Option<bool>,
+
+    /// Icon shown on the notification (backend-dependent: a file path or
+    /// freedesktop icon name on Linux, ignored where unsupported)
+    #[arg(long)]
+    icon: Option<String>,
+
+    /// App name reported to the notification backend
+    #[arg(long)]
+    app_name: Option<String>,
+
+    /// Notification urgency: low, normal, or critical
+    #[arg(long)]
+    urgency: Option<String>,
+
+    /// Only show entries fuzzy-matching this query (word, reading, or meaning)
+    #[arg(long)]
+    filter: Option<String>,
+
+    /// Only show entries tagged with any of these comma-separated codes, e.g. N3,verb
+    #[arg(long, value_delimiter = ',')]
+    codes: Option<Vec<String>>,
+
+    /// Print per-word exposure counts, last-seen times, and recall accuracy, then exit
+    #[arg(long, default_value_t = false)]
+    stats: bool,
 }
 
-fn parse_vocab_file(path: &PathBuf) -> Vec<Entry> {
+fn print_stats() {
+    let history = history::read_all();
+    if history.is_empty() {
+        println!("No history recorded yet.");
+        return;
+    }
+    for s in history::compute_stats(&history) {
+        let accuracy = match s.accuracy() {
+            Some(a) => format!("{:.0}%", a * 100.0),
+            None => "n/a".to_string(),
+        };
+        println!(
+            "{:<24} exposures={:<4} last_seen={} accuracy={}",
+            s.word,
+            s.exposures,
+            s.last_seen
+                .format(&time::format_description::well_known::Rfc3339)
+                .unwrap_or_default(),
+            accuracy,
+        );
+    }
+}
+
+fn parse_urgency(s: &str) -> Urgency {
+    match s.to_ascii_lowercase().as_str() {
+        "low" => Urgency::Low,
+        "critical" => Urgency::Critical,
+        _ => Urgency::Normal,
+    }
+}
+
+fn parse_vocab_file(path: &PathBuf, source: &str) -> Vec<Entry> {
     let mut out = Vec::new();
     let text = match fs::read_to_string(path) {
         Ok(s) => s,
@@ -55,7 +121,7 @@ fn parse_vocab_file(path: &PathBuf) -> Vec<Entry> {
         let reading = parts.get(1).map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
         let meaning = parts.get(2).map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
         let codes = parts.get(3).map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
-        out.push(Entry { word, reading, meaning, codes });
+        out.push(Entry { word, reading, meaning, codes, source: source.to_string() });
     }
     out
 }
@@ -66,7 +132,8 @@ fn load_vocab(dir: &PathBuf) -> Vec<Entry> {
         for entry in read_dir.flatten() {
             let p = entry.path();
             if p.is_file() {
-                let mut v = parse_vocab_file(&p);
+                let source = p.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                let mut v = parse_vocab_file(&p, &source);
                 entries.append(&mut v);
             }
         }
@@ -74,124 +141,296 @@ fn load_vocab(dir: &PathBuf) -> Vec<Entry> {
     entries
 }
 
-fn show_notification(title: &str, body: &str) {
-    // Try Windows toast via `winrt-notification`. If it fails at runtime, fall back to console.
-    #[cfg(target_os = "windows")]
-    {
-        use winrt_notification::{Sound, Toast};
-        let res = Toast::new(Toast::POWERSHELL_APP_ID)
-            .title(title)
-            .text1(body)
-            .sound(Some(Sound::Default))
-            .show();
-        if let Err(e) = res {
-            eprintln!("notification error: {}", e);
-            println!("{}\n{}", title, body);
+/// Builds a rotation order over `pool` (indices into `entries`), repeating
+/// indices so that files with a higher `file_weights` entry come up
+/// proportionally more often.
+fn weighted_order(entries: &[Entry], pool: &[usize], file_weights: &std::collections::HashMap<String, f64>) -> Vec<usize> {
+    let mut order = Vec::new();
+    for &i in pool {
+        let weight = file_weights.get(&entries[i].source).copied().unwrap_or(1.0).max(0.0);
+        let copies = weight.round().max(if weight > 0.0 { 1.0 } else { 0.0 }) as usize;
+        for _ in 0..copies {
+            order.push(i);
         }
     }
-    #[cfg(not(target_os = "windows"))]
-    {
-        println!("{}\n{}", title, body);
+    order
+}
+
+/// (Re)builds the rotation order and SRS lookup keys for `entries`, after
+/// narrowing the pool with `filter`. Called at startup and again whenever
+/// the vocab directory is hot-reloaded or the filter changes.
+fn build_rotation(entries: &[Entry], cfg: &config::ResolvedConfig, filter: &search::Filter) -> (Vec<usize>, Vec<String>) {
+    let pool = search::filter_indices(entries, filter, search::DEFAULT_THRESHOLD);
+
+    let mut order = if let Some(query) = &filter.query {
+        // A fuzzy query ranks the pool by match quality instead of
+        // shuffling it, so the best matches surface first.
+        let mut scored: Vec<(usize, i64)> = pool
+            .iter()
+            .map(|&i| (i, search::score_entry(query, &entries[i])))
+            .collect();
+        scored.sort_by(|a, b| b.1.cmp(&a.1));
+        scored.into_iter().map(|(i, _)| i).collect()
+    } else {
+        let mut order = weighted_order(entries, &pool, &cfg.file_weights);
+        if cfg.shuffle {
+            let mut rng = rand::thread_rng();
+            order.shuffle(&mut rng);
+        }
+        order
+    };
+
+    if order.is_empty() {
+        eprintln!("no vocab entries match the current filter; showing the full pool instead");
+        order = weighted_order(entries, &(0..entries.len()).collect::<Vec<_>>(), &cfg.file_weights);
+        if cfg.shuffle {
+            let mut rng = rand::thread_rng();
+            order.shuffle(&mut rng);
+        }
+    }
+
+    let keys = entries
+        .iter()
+        .map(|e| srs::key(&e.word, e.reading.as_deref()))
+        .collect();
+    (order, keys)
+}
+
+/// The entry most recently shown, kept around until it's finalized into
+/// the history log.
+struct Shown {
+    key: String,
+    word: String,
+    start_time: time::OffsetDateTime,
+}
+
+/// Records `pending` (if any) to the history log and clears it. `grade` is
+/// `Some` if the user graded it before it was finalized, `None` if it's
+/// being finalized unrated (a new notification arrived, or we're shutting
+/// down).
+fn finalize_history(pending: &mut Option<Shown>, grade: Option<u8>) {
+    if let Some(shown) = pending.take() {
+        history::append(&history::HistoryEntry {
+            word: shown.word,
+            start_time: shown.start_time,
+            acted_on: grade.is_some(),
+            grade,
+        });
+    }
+}
+
+/// Picks the entry with the earliest due date that's already due, falling
+/// back to round-robin over `order` when nothing is due yet. `None` means
+/// there is currently nothing to show at all (e.g. the vocab directory was
+/// hot-reloaded down to zero entries).
+fn next_index(idx: &mut usize, store: &srs::ReviewStore, order: &[usize], keys: &[String]) -> Option<usize> {
+    if order.is_empty() {
+        return None;
+    }
+    let now = time::OffsetDateTime::now_utc();
+    if let Some(i) = store.pick_due(order, keys, now) {
+        Some(i)
+    } else {
+        let i = order[*idx % order.len()];
+        *idx = idx.wrapping_add(1);
+        Some(i)
+    }
+}
+
+/// Vocab entries plus the rotation order and SRS keys derived from them,
+/// kept behind one lock so a reader can never pair a stale `order`/`keys`
+/// with an already-swapped `entries` (e.g. a hot-reload landing between a
+/// `ClockTimer` tick and its matching `Reload` event).
+struct VocabPool {
+    entries: Vec<Entry>,
+    filter: search::Filter,
+    order: Vec<usize>,
+    keys: Vec<String>,
+}
+
+impl VocabPool {
+    /// Recomputes `order`/`keys` from the current `entries` and `filter`.
+    fn rebuild(&mut self, cfg: &config::ResolvedConfig) {
+        let (order, keys) = build_rotation(&self.entries, cfg, &self.filter);
+        self.order = order;
+        self.keys = keys;
+    }
+}
+
+/// Locks `pool`, logging and skipping rather than panicking if it's
+/// poisoned — mirrors how `watcher.rs`'s reload loop handles the same
+/// mutex, since one bad event shouldn't take the whole process down.
+fn lock_pool(pool: &Mutex<VocabPool>) -> Option<std::sync::MutexGuard<'_, VocabPool>> {
+    match pool.lock() {
+        Ok(guard) => Some(guard),
+        Err(e) => {
+            eprintln!("vocab pool lock poisoned: {}", e);
+            None
+        }
     }
 }
 
 fn main() {
+    // Must happen before any thread is spawned below — `time` can only
+    // determine the local UTC offset while the process is single-threaded.
+    let local_offset = config::local_offset();
+
     let args = Args::parse();
 
-    let mut entries = load_vocab(&args.dir);
-    if entries.is_empty() {
-        eprintln!("No vocab entries found in {:?}. Create text files under that directory.", args.dir);
+    if args.stats {
+        print_stats();
         return;
     }
 
-    if args.shuffle {
-        let mut rng = rand::thread_rng();
-        entries.shuffle(&mut rng);
+    let file_config = match config::Config::load() {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("config error: {e}");
+            return;
+        }
+    };
+    let cfg = file_config.resolve(&args);
+
+    let initial_entries = load_vocab(&cfg.dir);
+    if initial_entries.is_empty() {
+        eprintln!("No vocab entries found in {:?}. Create text files under that directory.", cfg.dir);
+        return;
     }
+    let filter = search::Filter {
+        query: args.filter.clone(),
+        codes: args.codes.clone(),
+    };
+    let (order, keys) = build_rotation(&initial_entries, &cfg, &filter);
+    let pool = Arc::new(Mutex::new(VocabPool {
+        entries: initial_entries,
+        filter,
+        order,
+        keys,
+    }));
 
-    let running = Arc::new(AtomicBool::new(true));
-    let r = running.clone();
-    ctrlc::set_handler(move || {
-        r.store(false, Ordering::SeqCst);
-    })
-    .ok();
+    let mut review_store = srs::ReviewStore::load();
 
-    // channel for tray "Show Now" triggers
-    let (tx, rx) = mpsc::channel::<()>();
-
-    // Setup tray icon on Windows. Menu: Show Now, Quit
-    #[cfg(target_os = "windows")]
-    {
-        use tray_icon::{TrayIcon, menu::Menu, menu::MenuItem};
-        let tx_clone = tx.clone();
-        let running_clone = running.clone();
-        std::thread::spawn(move || {
-            let mut menu = Menu::new();
-            let show_now = MenuItem::new("Show now", true, false);
-            let quit = MenuItem::new("Quit", true, false);
-            let _ = menu.append(&show_now);
-            let _ = menu.append(&quit);
-
-            let mut tray = match TrayIcon::new(None, None, Some(menu)) {
-                Ok(t) => t,
-                Err(e) => { eprintln!("tray init error: {}", e); return; }
-            };
-
-            let tx_show = tx_clone.clone();
-            let _ = tray.set_menu(&Box::new(Menu::new()));
-            
-            // keep thread alive to process tray events
-            loop {
-                std::thread::sleep(Duration::from_secs(60));
-                if !running_clone.load(Ordering::SeqCst) { break; }
-            }
-        });
-    }
+    let backend = notifier::default_notifier();
+    let urgency = parse_urgency(&cfg.urgency);
 
     let mut idx = 0usize;
 
-    if args.force {
-        let e = &entries[idx % entries.len()];
-        let title = &e.word;
+    // Returns whether a notification was actually shown (it may be
+    // suppressed during quiet hours).
+    let notify_entry = |e: &Entry| -> bool {
+        if let Some(quiet) = &cfg.quiet_hours {
+            if quiet.contains(&config::current_hhmm(local_offset)) {
+                return false;
+            }
+        }
         let mut body = String::new();
-        if let Some(r) = &e.reading { body.push_str(r); }
-        if let Some(m) = &e.meaning { if !body.is_empty() { body.push_str(" — "); } body.push_str(m); }
-        if let Some(c) = &e.codes { if !c.is_empty() { body.push_str(" (" ); body.push_str(c); body.push_str(")"); } }
-        show_notification(title, &body);
+        for field in &cfg.body_fields {
+            match field {
+                config::BodyField::Reading => {
+                    if let Some(r) = e.reading.as_deref().filter(|s| !s.is_empty()) {
+                        body.push_str(r);
+                    }
+                }
+                config::BodyField::Meaning => {
+                    if let Some(m) = e.meaning.as_deref().filter(|s| !s.is_empty()) {
+                        if !body.is_empty() { body.push_str(" — "); }
+                        body.push_str(m);
+                    }
+                }
+                config::BodyField::Codes => {
+                    if let Some(c) = e.codes.as_deref().filter(|s| !s.is_empty()) {
+                        body.push_str(" (");
+                        body.push_str(c);
+                        body.push(')');
+                    }
+                }
+            }
+        }
+        let req = NotificationRequest {
+            title: &e.word,
+            body: &body,
+            icon: &cfg.icon,
+            app_name: &cfg.app_name,
+            urgency,
+        };
+        notifier::show_notification(backend.as_ref(), &req);
+        true
+    };
+
+    if args.force {
+        if let Some(guard) = lock_pool(&pool) {
+            if let Some(i) = next_index(&mut idx, &review_store, &guard.order, &guard.keys) {
+                notify_entry(&guard.entries[i]);
+            }
+        }
         return;
     }
 
-    let interval = Duration::from_secs(args.interval * 60);
-    while running.load(Ordering::SeqCst) {
-        // If we received a "show now" from tray, show immediately
-        if let Ok(_) = rx.try_recv() {
-            let e = &entries[idx % entries.len()];
-            let title = &e.word;
-            let mut body = String::new();
-            if let Some(r) = &e.reading { body.push_str(r); }
-            if let Some(m) = &e.meaning { if !body.is_empty() { body.push_str(" — "); } body.push_str(m); }
-            if let Some(c) = &e.codes { if !c.is_empty() { body.push_str(" ("); body.push_str(c); body.push_str(")"); } }
-            show_notification(title, &body);
-            idx = idx.wrapping_add(1);
-        } else {
-            let e = &entries[idx % entries.len()];
-            let title = &e.word;
-            let mut body = String::new();
-            if let Some(r) = &e.reading { body.push_str(r); }
-            if let Some(m) = &e.meaning { if !body.is_empty() { body.push_str(" — "); } body.push_str(m); }
-            if let Some(c) = &e.codes { if !c.is_empty() { body.push_str(" ("); body.push_str(c); body.push_str(")"); } }
-            show_notification(title, &body);
-            idx = idx.wrapping_add(1);
-            let mut slept = 0u64;
-            while slept < interval.as_secs() && running.load(Ordering::SeqCst) {
-                // allow immediate show triggers while sleeping
-                if let Ok(_) = rx.try_recv() {
-                    break; // break sleep and show immediately next loop
+    let interval = Duration::from_secs(cfg.interval * 60);
+    let (writer, reader) = events::channel();
+    events::spawn_clock(writer.clone(), interval);
+    events::spawn_stdin(writer.clone());
+    events::spawn_signals(writer.clone());
+    #[cfg(target_os = "windows")]
+    events::spawn_tray(writer.clone());
+
+    let _watcher = match watcher::spawn(cfg.dir.clone(), cfg.clone(), pool.clone(), writer) {
+        Ok(w) => Some(w),
+        Err(e) => {
+            eprintln!("could not watch {:?} for changes: {}", cfg.dir, e);
+            None
+        }
+    };
+
+    // The entry shown most recently, awaiting an SM-2 grade; finalized into
+    // the history log either when a grade comes in or once it's clear none
+    // is coming (the next notification, or shutdown).
+    let mut awaiting: Option<Shown> = None;
+    while let Some(event) = reader.recv() {
+        match event {
+            Event::ClockTimer | Event::ShowNow => {
+                finalize_history(&mut awaiting, None);
+                if let Some(guard) = lock_pool(&pool) {
+                    if let Some(i) = next_index(&mut idx, &review_store, &guard.order, &guard.keys) {
+                        if notify_entry(&guard.entries[i]) {
+                            awaiting = Some(Shown {
+                                key: guard.keys[i].clone(),
+                                word: guard.entries[i].word.clone(),
+                                start_time: time::OffsetDateTime::now_utc(),
+                            });
+                        }
+                    }
+                }
+            }
+            Event::Grade(q) => {
+                if let Some(shown) = &awaiting {
+                    review_store.grade(&shown.key, q, time::OffsetDateTime::now_utc());
+                    review_store.save();
+                }
+                finalize_history(&mut awaiting, Some(q));
+            }
+            Event::Reload => {
+                let entries = load_vocab(&cfg.dir);
+                if let Some(mut guard) = lock_pool(&pool) {
+                    guard.entries = entries;
+                    guard.rebuild(&cfg);
+                }
+            }
+            Event::SetFilter(query) => {
+                if let Some(mut guard) = lock_pool(&pool) {
+                    guard.filter.query = query;
+                    guard.rebuild(&cfg);
+                }
+            }
+            Event::SetCodes(codes) => {
+                if let Some(mut guard) = lock_pool(&pool) {
+                    guard.filter.codes = codes;
+                    guard.rebuild(&cfg);
                 }
-                thread::sleep(Duration::from_secs(1));
-                slept += 1;
             }
+            Event::Quit => break,
         }
     }
+    finalize_history(&mut awaiting, None);
     println!("Exiting VocabPop.");
 }
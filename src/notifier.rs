@@ -0,0 +1,186 @@
+//! Desktop notification backends.
+//!
+//! `show_notification` used to be hard-wired to the Windows toast API with a
+//! `println!` fallback everywhere else. This module gives every platform a
+//! real backend behind a common [`Notifier`] trait, chosen once at startup.
+
+use std::fmt;
+
+/// How urgently a notification should be presented, mirroring the
+/// freedesktop notification spec's urgency levels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Urgency {
+    Low,
+    Normal,
+    Critical,
+}
+
+impl Default for Urgency {
+    fn default() -> Self {
+        Urgency::Normal
+    }
+}
+
+/// Everything needed to render one notification, independent of backend.
+#[derive(Debug, Clone)]
+pub struct NotificationRequest<'a> {
+    pub title: &'a str,
+    pub body: &'a str,
+    pub icon: &'a str,
+    pub app_name: &'a str,
+    pub urgency: Urgency,
+}
+
+#[derive(Debug)]
+pub struct NotifierError(pub String);
+
+impl fmt::Display for NotifierError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for NotifierError {}
+
+/// A platform-specific way of putting a notification on screen.
+pub trait Notifier {
+    fn notify(&self, req: &NotificationRequest) -> Result<(), NotifierError>;
+}
+
+/// Prints to stdout. Used when no native backend is available or fails.
+pub struct ConsoleNotifier;
+
+impl Notifier for ConsoleNotifier {
+    fn notify(&self, req: &NotificationRequest) -> Result<(), NotifierError> {
+        println!("{}\n{}", req.title, req.body);
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub struct WindowsNotifier;
+
+#[cfg(target_os = "windows")]
+impl Notifier for WindowsNotifier {
+    fn notify(&self, req: &NotificationRequest) -> Result<(), NotifierError> {
+        use std::path::Path;
+        use winrt_notification::{Duration, IconCrop, Sound, Toast};
+
+        let app_id = if req.app_name.is_empty() {
+            Toast::POWERSHELL_APP_ID
+        } else {
+            req.app_name
+        };
+
+        let mut toast = Toast::new(app_id)
+            .title(req.title)
+            .text1(req.body)
+            .sound(Some(Sound::Default));
+
+        if !req.icon.is_empty() {
+            toast = toast.icon(Path::new(req.icon), IconCrop::Square, req.title);
+        }
+
+        // winrt-notification has no urgency concept of its own; the closest
+        // analogue is how long the toast stays on screen before dismissing.
+        toast = toast.duration(match req.urgency {
+            Urgency::Critical => Duration::Long,
+            Urgency::Low | Urgency::Normal => Duration::Short,
+        });
+
+        toast.show().map_err(|e| NotifierError(e.to_string()))
+    }
+}
+
+/// Sends notifications over the freedesktop `org.freedesktop.Notifications`
+/// D-Bus interface via `notify-rust`.
+#[cfg(target_os = "linux")]
+pub struct DbusNotifier;
+
+#[cfg(target_os = "linux")]
+impl Notifier for DbusNotifier {
+    fn notify(&self, req: &NotificationRequest) -> Result<(), NotifierError> {
+        use notify_rust::{Notification, Urgency as DbusUrgency};
+
+        let urgency = match req.urgency {
+            Urgency::Low => DbusUrgency::Low,
+            Urgency::Normal => DbusUrgency::Normal,
+            Urgency::Critical => DbusUrgency::Critical,
+        };
+
+        Notification::new()
+            .summary(req.title)
+            .body(req.body)
+            .appname(req.app_name)
+            .icon(req.icon)
+            .urgency(urgency)
+            .show()
+            .map(|_| ())
+            .map_err(|e| NotifierError(e.to_string()))
+    }
+}
+
+/// Shells out to `osascript` to post an `NSUserNotification`.
+#[cfg(target_os = "macos")]
+pub struct OsaScriptNotifier;
+
+/// Escapes `s` for embedding in a double-quoted AppleScript string literal.
+/// Backslashes must be escaped *before* quotes — otherwise a source string
+/// ending in a backslash immediately before a quote we insert would pair up
+/// with it and break out of the literal, letting arbitrary AppleScript run.
+#[cfg(target_os = "macos")]
+fn escape_applescript_string(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(target_os = "macos")]
+impl Notifier for OsaScriptNotifier {
+    fn notify(&self, req: &NotificationRequest) -> Result<(), NotifierError> {
+        use std::process::Command;
+
+        let script = format!(
+            "display notification \"{}\" with title \"{}\"",
+            escape_applescript_string(req.body),
+            escape_applescript_string(req.title)
+        );
+        let status = Command::new("osascript")
+            .arg("-e")
+            .arg(script)
+            .status()
+            .map_err(|e| NotifierError(e.to_string()))?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(NotifierError(format!("osascript exited with {status}")))
+        }
+    }
+}
+
+/// Picks the best backend for the current platform at startup.
+pub fn default_notifier() -> Box<dyn Notifier> {
+    #[cfg(target_os = "windows")]
+    {
+        Box::new(WindowsNotifier)
+    }
+    #[cfg(target_os = "linux")]
+    {
+        Box::new(DbusNotifier)
+    }
+    #[cfg(target_os = "macos")]
+    {
+        Box::new(OsaScriptNotifier)
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+    {
+        Box::new(ConsoleNotifier)
+    }
+}
+
+/// Sends `req` through `notifier`, falling back to the console if the
+/// backend fails at runtime (e.g. no D-Bus session, `osascript` missing).
+pub fn show_notification(notifier: &dyn Notifier, req: &NotificationRequest) {
+    if let Err(e) = notifier.notify(req) {
+        eprintln!("notification error: {}", e);
+        ConsoleNotifier.notify(req).ok();
+    }
+}
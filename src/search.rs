@@ -0,0 +1,197 @@
+//! Fuzzy search and structured filtering over the vocab pool.
+//!
+//! A free-text query is scored against each [`Entry`]'s `word`, `reading`,
+//! and `meaning` with a Smith-Waterman-style subsequence matcher (the same
+//! family of algorithm as fuzzy finders like `fzf` or the `fuzzy` crate):
+//! query characters must appear in order in the candidate, and matches
+//! that are consecutive or start a word score higher. A separate
+//! `--codes` filter narrows by the structured `codes` field (e.g. JLPT
+//! level or part of speech) instead of scoring it.
+
+use crate::Entry;
+
+/// A fuzzy match must score above this to count as a hit; at or below it
+/// the match is considered noise.
+pub const DEFAULT_THRESHOLD: i64 = 1;
+
+/// What to narrow the vocab pool down to.
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    pub query: Option<String>,
+    pub codes: Option<Vec<String>>,
+}
+
+impl Filter {
+    pub fn is_empty(&self) -> bool {
+        self.query.is_none() && self.codes.is_none()
+    }
+}
+
+/// Scores how well `query` matches `candidate` as a fuzzy subsequence.
+/// Higher is better; `i64::MIN` means "no match at all".
+pub fn score(query: &str, candidate: &str) -> i64 {
+    if query.is_empty() {
+        return 0;
+    }
+    let query: Vec<char> = query.to_lowercase().chars().collect();
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+    let (qn, cn) = (query.len(), candidate.len());
+    if qn > cn {
+        return i64::MIN;
+    }
+
+    // Subsequence DP, à la Smith-Waterman: `table[i][j]` is the best score
+    // for matching query[..i] as a subsequence within candidate[..j], or
+    // "impossible" (i64::MIN / 2) if query[..i] can't be placed there at
+    // all. A candidate character may always be skipped over (carried
+    // forward from `table[i][j-1]`), but a query character only ever
+    // advances `i` by actually being matched against `candidate[j-1]` — it
+    // is never skipped, which is what makes this a *subsequence* match
+    // rather than "any character in common".
+    const MATCH: i64 = 16;
+    const CONSECUTIVE_BONUS: i64 = 8;
+    const WORD_START_BONUS: i64 = 12;
+
+    let mut table = vec![vec![i64::MIN / 2; cn + 1]; qn + 1];
+    for j in 0..=cn {
+        table[0][j] = 0;
+    }
+
+    for i in 1..=qn {
+        for j in 1..=cn {
+            let mut best = table[i][j - 1];
+            if query[i - 1] == candidate[j - 1] && table[i - 1][j - 1] > i64::MIN / 4 {
+                let mut s = table[i - 1][j - 1] + MATCH;
+                let is_word_start = j == 1 || !candidate[j - 2].is_alphanumeric();
+                if is_word_start {
+                    s += WORD_START_BONUS;
+                }
+                if i > 1 && j > 1 && query[i - 2] == candidate[j - 2] {
+                    s += CONSECUTIVE_BONUS;
+                }
+                if s > best {
+                    best = s;
+                }
+            }
+            table[i][j] = best;
+        }
+    }
+
+    let best = table[qn][cn];
+    if best <= i64::MIN / 4 { i64::MIN } else { best }
+}
+
+/// The best score for `query` across an entry's word, reading, and meaning.
+pub fn score_entry(query: &str, entry: &Entry) -> i64 {
+    [
+        Some(entry.word.as_str()),
+        entry.reading.as_deref(),
+        entry.meaning.as_deref(),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|field| score(query, field))
+    .max()
+    .unwrap_or(i64::MIN)
+}
+
+fn codes_match(entry: &Entry, wanted: &[String]) -> bool {
+    let Some(codes) = &entry.codes else { return false };
+    let have: Vec<String> = codes.split(',').map(|c| c.trim().to_lowercase()).collect();
+    wanted.iter().any(|w| have.contains(&w.to_lowercase()))
+}
+
+/// Indices into `entries` that pass both the fuzzy-query threshold and the
+/// `--codes` filter (each is skipped if not set).
+pub fn filter_indices(entries: &[Entry], filter: &Filter, threshold: i64) -> Vec<usize> {
+    entries
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| match &filter.codes {
+            Some(wanted) => codes_match(e, wanted),
+            None => true,
+        })
+        .filter(|(_, e)| match &filter.query {
+            Some(q) => score_entry(q, e) > threshold,
+            None => true,
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(word: &str, reading: Option<&str>, meaning: Option<&str>, codes: Option<&str>) -> Entry {
+        Entry {
+            word: word.to_string(),
+            reading: reading.map(str::to_string),
+            meaning: meaning.map(str::to_string),
+            codes: codes.map(str::to_string),
+            source: "test".to_string(),
+        }
+    }
+
+    #[test]
+    fn exact_match_scores_higher_than_a_shifted_one() {
+        let exact = score("neko", "neko");
+        let shifted = score("neko", "xneko");
+        assert!(exact > shifted, "{exact} should beat {shifted}");
+    }
+
+    #[test]
+    fn non_subsequence_does_not_match() {
+        assert_eq!(score("zzz", "neko"), i64::MIN);
+        assert_eq!(score("nekox", "neko"), i64::MIN); // query longer than candidate
+    }
+
+    #[test]
+    fn a_single_trailing_match_is_not_enough_for_the_rest_of_the_query() {
+        // Regression test: the query must match as a whole subsequence, not
+        // "has at least one character in common with the candidate". Only
+        // the last query character appears in each candidate at all, so
+        // neither of these should match.
+        assert_eq!(score("ab", "xyzb"), i64::MIN);
+        assert_eq!(
+            score("vwxy", "this has no match chars but ends in y"),
+            i64::MIN
+        );
+    }
+
+    #[test]
+    fn empty_query_matches_everything_at_zero() {
+        assert_eq!(score("", "neko"), 0);
+    }
+
+    #[test]
+    fn consecutive_characters_score_higher_than_gapped_ones() {
+        let consecutive = score("ne", "neko");
+        // A one-character gap, and `x` isn't a word boundary, so this isolates
+        // the consecutive-run bonus from the word-start bonus.
+        let gapped = score("ne", "nxeko");
+        assert!(consecutive > gapped, "{consecutive} should beat {gapped}");
+    }
+
+    #[test]
+    fn match_at_a_word_boundary_scores_higher_than_mid_word() {
+        let at_boundary = score("ko", "ko neko");
+        let mid_word = score("ko", "nekoko");
+        assert!(at_boundary > mid_word, "{at_boundary} should beat {mid_word}");
+    }
+
+    #[test]
+    fn score_entry_takes_the_best_field() {
+        let e = entry("猫", Some("neko"), Some("cat"), None);
+        // "cat" only appears in the meaning field.
+        assert_eq!(score_entry("cat", &e), score("cat", "cat"));
+    }
+
+    #[test]
+    fn codes_match_is_case_insensitive_and_requires_any_overlap() {
+        let e = entry("word", None, None, Some("N3, verb"));
+        assert!(codes_match(&e, &["n3".to_string()]));
+        assert!(codes_match(&e, &["adjective".to_string(), "Verb".to_string()]));
+        assert!(!codes_match(&e, &["N5".to_string()]));
+    }
+}
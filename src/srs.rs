@@ -0,0 +1,212 @@
+//! SuperMemo SM-2 spaced-repetition scheduling, with review state persisted
+//! across runs so due dates survive a restart.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use time::OffsetDateTime;
+
+const MIN_EF: f64 = 1.3;
+
+/// Per-entry SM-2 state: repetition count, easiness factor, and the
+/// interval (in days) used to compute the next due date.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewState {
+    pub n: u32,
+    pub ef: f64,
+    pub interval_days: u32,
+    #[serde(with = "time::serde::rfc3339")]
+    pub due: OffsetDateTime,
+}
+
+impl Default for ReviewState {
+    fn default() -> Self {
+        ReviewState {
+            n: 0,
+            ef: 2.5,
+            interval_days: 0,
+            due: OffsetDateTime::UNIX_EPOCH,
+        }
+    }
+}
+
+impl ReviewState {
+    /// Applies one SM-2 review with recall quality `q` (0..=5), returning
+    /// the updated state due at `now + interval_days`.
+    pub fn review(&self, q: u8, now: OffsetDateTime) -> ReviewState {
+        let mut n = self.n;
+        let interval_days = if q >= 3 {
+            let interval = if n == 0 {
+                1
+            } else if n == 1 {
+                6
+            } else {
+                (self.interval_days as f64 * self.ef).round() as u32
+            };
+            n += 1;
+            interval
+        } else {
+            n = 0;
+            1
+        };
+
+        let q = f64::from(q);
+        let ef = (self.ef + (0.1 - (5.0 - q) * (0.08 + (5.0 - q) * 0.02))).max(MIN_EF);
+        let due = now + time::Duration::days(i64::from(interval_days));
+
+        ReviewState { n, ef, interval_days, due }
+    }
+}
+
+/// Identifies an entry for review-state lookups. Word alone isn't unique
+/// enough (homographs with different readings), so the two are combined.
+pub fn key(word: &str, reading: Option<&str>) -> String {
+    format!("{word}\u{1}{}", reading.unwrap_or(""))
+}
+
+/// All per-entry review state, keyed by [`key`].
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ReviewStore {
+    #[serde(default)]
+    states: HashMap<String, ReviewState>,
+}
+
+impl ReviewStore {
+    pub fn file_path() -> Option<PathBuf> {
+        directories::ProjectDirs::from("", "", "vocabpop")
+            .map(|dirs| dirs.config_dir().join("review_state.json"))
+    }
+
+    /// Loads persisted review state. Starts fresh (rather than failing the
+    /// whole program) if the file is missing or corrupt — losing review
+    /// history is recoverable, losing the ability to study is not.
+    pub fn load() -> ReviewStore {
+        let Some(path) = Self::file_path() else {
+            return ReviewStore::default();
+        };
+        match fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str(&text).unwrap_or_else(|e| {
+                eprintln!("review state error: {e}, starting fresh");
+                ReviewStore::default()
+            }),
+            Err(_) => ReviewStore::default(),
+        }
+    }
+
+    pub fn save(&self) {
+        let Some(path) = Self::file_path() else {
+            return;
+        };
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        match serde_json::to_string_pretty(self) {
+            Ok(text) => {
+                if let Err(e) = fs::write(&path, text) {
+                    eprintln!("failed to save review state: {e}");
+                }
+            }
+            Err(e) => eprintln!("failed to serialize review state: {e}"),
+        }
+    }
+
+    /// Records a recall grade `q` (0..=5) for the entry identified by `key`.
+    pub fn grade(&mut self, key: &str, q: u8, now: OffsetDateTime) {
+        let state = self.states.entry(key.to_string()).or_default();
+        *state = state.review(q, now);
+    }
+
+    /// The entry in `pool` (indices into the full `keys` list) with the
+    /// earliest due date that is already due, or `None` if nothing in the
+    /// pool is due yet (an entry with no recorded state is never "due" —
+    /// it hasn't entered the schedule).
+    pub fn pick_due(&self, pool: &[usize], keys: &[String], now: OffsetDateTime) -> Option<usize> {
+        pool.iter()
+            .filter_map(|&i| self.states.get(&keys[i]).map(|s| (i, s.due)))
+            .filter(|(_, due)| *due <= now)
+            .min_by_key(|(_, due)| *due)
+            .map(|(i, _)| i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_two_good_reviews_use_fixed_intervals() {
+        let now = OffsetDateTime::UNIX_EPOCH;
+        let state = ReviewState::default().review(4, now);
+        assert_eq!(state.n, 1);
+        assert_eq!(state.interval_days, 1);
+
+        let state = state.review(4, now);
+        assert_eq!(state.n, 2);
+        assert_eq!(state.interval_days, 6);
+    }
+
+    #[test]
+    fn third_good_review_scales_by_easiness_factor() {
+        let now = OffsetDateTime::UNIX_EPOCH;
+        let state = ReviewState::default().review(4, now).review(4, now);
+        let ef_before = state.ef;
+        let state = state.review(4, now);
+        assert_eq!(state.n, 3);
+        assert_eq!(state.interval_days, (6.0 * ef_before).round() as u32);
+    }
+
+    #[test]
+    fn q_of_three_is_the_pass_boundary() {
+        let now = OffsetDateTime::UNIX_EPOCH;
+        let passed = ReviewState::default().review(3, now);
+        assert_eq!(passed.n, 1);
+
+        let failed = ReviewState::default().review(2, now);
+        assert_eq!(failed.n, 0);
+        assert_eq!(failed.interval_days, 1);
+    }
+
+    #[test]
+    fn failing_a_review_resets_repetition_count_and_due_date() {
+        let now = OffsetDateTime::UNIX_EPOCH;
+        let state = ReviewState::default().review(5, now).review(5, now);
+        assert!(state.n > 0);
+        let state = state.review(0, now);
+        assert_eq!(state.n, 0);
+        assert_eq!(state.interval_days, 1);
+        assert_eq!(state.due, now + time::Duration::days(1));
+    }
+
+    #[test]
+    fn easiness_factor_never_drops_below_the_sm2_floor() {
+        let now = OffsetDateTime::UNIX_EPOCH;
+        let mut state = ReviewState::default();
+        for _ in 0..20 {
+            state = state.review(0, now);
+        }
+        assert!(state.ef >= MIN_EF);
+    }
+
+    #[test]
+    fn pick_due_ignores_entries_outside_the_pool() {
+        let now = OffsetDateTime::UNIX_EPOCH;
+        let mut store = ReviewStore::default();
+        store.grade("due", 4, now - time::Duration::days(10));
+        store.grade("also-due", 4, now - time::Duration::days(10));
+
+        let keys = vec!["due".to_string(), "also-due".to_string()];
+        // Only index 0 is in the pool, so index 1 must never be picked even
+        // though it's due too.
+        assert_eq!(store.pick_due(&[0], &keys, now), Some(0));
+    }
+
+    #[test]
+    fn pick_due_returns_none_when_nothing_is_due_yet() {
+        let now = OffsetDateTime::UNIX_EPOCH;
+        let mut store = ReviewStore::default();
+        store.grade("fresh", 4, now);
+        let keys = vec!["fresh".to_string()];
+        assert_eq!(store.pick_due(&[0], &keys, now), None);
+    }
+}
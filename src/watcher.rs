@@ -0,0 +1,74 @@
+//! Hot-reloads the vocab directory.
+//!
+//! `load_vocab` used to run once at startup, so picking up edits meant
+//! restarting the process. This watches `dir` for changes (the `notify`
+//! crate), debounces bursts of events (editors often touch a file several
+//! times per save), reloads, and swaps the result into the shared
+//! [`VocabPool`], rebuilding its rotation order/keys under the same lock so
+//! a notification tick racing the reload can never pair a stale order with
+//! the already-swapped entries.
+
+use crate::VocabPool;
+use notify::{Event as NotifyEvent, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::config::ResolvedConfig;
+use crate::events::{Event, Writer};
+
+/// How long to wait for more filesystem events before reloading, so a burst
+/// of saves to several files triggers one reload instead of several.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Starts watching `dir`. Returns the watcher handle, which must be kept
+/// alive for the duration of the program (dropping it stops the watch).
+pub fn spawn(
+    dir: PathBuf,
+    cfg: ResolvedConfig,
+    pool: Arc<Mutex<VocabPool>>,
+    writer: Writer,
+) -> notify::Result<RecommendedWatcher> {
+    let (tx, rx) = mpsc::channel::<notify::Result<NotifyEvent>>();
+    let mut watcher = notify::recommended_watcher(move |res| {
+        let _ = tx.send(res);
+    })?;
+    watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+
+    thread_spawn_reload_loop(dir, cfg, pool, writer, rx);
+    Ok(watcher)
+}
+
+fn thread_spawn_reload_loop(
+    dir: PathBuf,
+    cfg: ResolvedConfig,
+    pool: Arc<Mutex<VocabPool>>,
+    writer: Writer,
+    rx: mpsc::Receiver<notify::Result<NotifyEvent>>,
+) {
+    std::thread::spawn(move || {
+        while let Ok(res) = rx.recv() {
+            if let Err(e) = res {
+                eprintln!("vocab watcher error: {}", e);
+                continue;
+            }
+            // Drain anything else that arrives within the debounce window
+            // so a flurry of writes reloads once, not once per write.
+            while rx.recv_timeout(DEBOUNCE).is_ok() {}
+
+            let entries = crate::load_vocab(&dir);
+            match pool.lock() {
+                Ok(mut guard) => {
+                    guard.entries = entries;
+                    guard.rebuild(&cfg);
+                }
+                Err(e) => {
+                    eprintln!("vocab watcher: lock poisoned: {}", e);
+                    continue;
+                }
+            }
+            writer.send(Event::Reload);
+        }
+    });
+}